@@ -1,95 +1,655 @@
 use wasm_bindgen::prelude::*;
-use oxigraph::sparql::{QueryResults, QuerySolution};
-use oxigraph::store::{Store, StoreBuilder};
-use oxigraph::model::{Graph, NamedNode, Triple};
-use oxigraph::io::GraphFormat;
+use serde::{Deserialize, Serialize};
+use oxigraph::sparql::results::QueryResultsFormat;
+use oxigraph::sparql::{QueryResults, Update};
+use oxigraph::store::Store;
+use oxigraph::model::{Graph, NamedNode, Subject, Term, TermRef, Triple};
+use oxigraph::io::{RdfFormat, RdfParseError, RdfParser, RdfSerializer};
 use std::io::Cursor;
 
+mod conformance;
+
 #[wasm_bindgen]
 pub struct RDFEngine {
-    store: Store,
+    pub(crate) store: Store,
+}
+
+/// A single recoverable parse failure, positioned for display in an editor.
+#[derive(Serialize, Deserialize)]
+pub struct ParseIssue {
+    pub line: u64,
+    pub column: u64,
+    pub message: String,
+}
+
+/// Net effect of a `SPARQL 1.1 Update` request on the store.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateReport {
+    pub inserted: u64,
+    pub deleted: u64,
+}
+
+/// Error type for the engine's internal logic, kept independent of `wasm_bindgen::JsError`.
+/// `JsError`'s constructor calls into an imported JS function, so it can only ever be
+/// built inside a real wasm runtime and panics under plain `cargo test`. Core methods
+/// return `EngineError` so they stay testable natively; only the `#[wasm_bindgen]`
+/// boundary methods convert the result into `JsError`.
+#[derive(Debug)]
+pub(crate) struct EngineError(String);
+
+impl EngineError {
+    fn msg(message: impl Into<String>) -> Self {
+        EngineError(message.into())
+    }
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<E: std::error::Error> From<E> for EngineError {
+    fn from(error: E) -> Self {
+        EngineError(error.to_string())
+    }
+}
+
+impl From<EngineError> for JsError {
+    fn from(error: EngineError) -> Self {
+        JsError::new(&error.0)
+    }
 }
 
 #[wasm_bindgen]
 impl RDFEngine {
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
-        let store = StoreBuilder::new().build();
-        RDFEngine { store }
+    pub fn new() -> Result<RDFEngine, JsError> {
+        Ok(RDFEngine { store: Store::new()? })
     }
 
+    // `results_format`: for SELECT/ASK, one of "json"/"xml"/"csv"/"tsv" (the W3C
+    // SPARQL Query Results formats); for CONSTRUCT/DESCRIBE, "turtle" or "ntriples".
     #[wasm_bindgen]
-    pub fn execute_query(&self, query: &str) -> Result<JsValue, JsError> {
+    pub fn execute_query(&self, query: &str, results_format: &str) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.execute_query_str(query, results_format)?)?)
+    }
+
+    fn execute_query_str(&self, query: &str, results_format: &str) -> Result<String, EngineError> {
         let results = self.store.query(query)?;
-        let json_results = match results {
-            QueryResults::Solutions(solutions) => {
-                let mut rows = Vec::new();
-                for solution in solutions {
-                    let mut row = serde_json::Map::new();
-                    for (var, term) in solution.iter() {
-                        row.insert(var.to_string(), term.to_string().into());
-                    }
-                    rows.push(serde_json::Value::Object(row));
+        let serialized = match results {
+            QueryResults::Graph(triples) => {
+                let format = Self::graph_result_format_from_name(results_format)?;
+                let mut output = Vec::new();
+                let mut writer = RdfSerializer::from_format(format).for_writer(&mut output);
+                for triple in triples {
+                    writer.serialize_triple(&triple?)?;
                 }
-                serde_json::Value::Array(rows)
+                writer.finish()?;
+                String::from_utf8(output)?
             }
-            QueryResults::Boolean(value) => value.into(),
-            QueryResults::Graph(graph) => {
-                let mut triples = Vec::new();
-                for triple in graph.iter() {
-                    triples.push(format!("{} {} {}", triple.subject, triple.predicate, triple.object));
-                }
-                serde_json::Value::Array(triples.into_iter().map(|s| s.into()).collect())
+            solutions_or_boolean => {
+                let format = Self::query_results_format_from_name(results_format)?;
+                let mut output = Vec::new();
+                solutions_or_boolean.write(&mut output, format)?;
+                String::from_utf8(output)?
             }
         };
-        Ok(serde_wasm_bindgen::to_value(&json_results)?)
+        Ok(serialized)
+    }
+
+    fn query_results_format_from_name(name: &str) -> Result<QueryResultsFormat, EngineError> {
+        match name {
+            "json" => Ok(QueryResultsFormat::Json),
+            "xml" => Ok(QueryResultsFormat::Xml),
+            "csv" => Ok(QueryResultsFormat::Csv),
+            "tsv" => Ok(QueryResultsFormat::Tsv),
+            other => Err(EngineError::msg(format!("unsupported SPARQL results format: {other}"))),
+        }
     }
 
+    fn graph_result_format_from_name(name: &str) -> Result<RdfFormat, EngineError> {
+        match name {
+            "turtle" => Ok(RdfFormat::Turtle),
+            "ntriples" => Ok(RdfFormat::NTriples),
+            other => Err(EngineError::msg(format!("unsupported graph results format: {other} (expected turtle or ntriples)"))),
+        }
+    }
+
+    // `format`: "turtle", "ntriples", "nquads", "trig", "rdfxml", or "n3". The
+    // Turtle-family formats recover statement-by-statement instead of aborting on
+    // the first syntax error. `graph_name`, if given, retargets default-graph
+    // triples (turtle/ntriples/rdfxml/n3 carry no graph of their own); it has no
+    // effect on trig/nquads statements that already name a graph.
     #[wasm_bindgen]
-    pub fn execute_update(&mut self, ttl: &str) -> Result<bool, JsError> {
-        let mut graph = Graph::new();
-        let cursor = Cursor::new(ttl.as_bytes());
-        graph.read_from(cursor, GraphFormat::Turtle)?;
-        
-        for triple in graph.iter() {
-            self.store.insert(triple)?;
+    pub fn ingest_rdf(&mut self, data: &str, format: &str, graph_name: Option<String>) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.ingest_rdf_issues(data, format, graph_name)?)?)
+    }
+
+    fn ingest_rdf_issues(
+        &mut self,
+        data: &str,
+        format: &str,
+        graph_name: Option<String>,
+    ) -> Result<Vec<ParseIssue>, EngineError> {
+        let rdf_format = Self::rdf_format_from_name(format)?;
+        let target_graph = graph_name.map(NamedNode::new).transpose()?;
+        let issues = if rdf_format == RdfFormat::RdfXml {
+            let mut parser = RdfParser::from_format(rdf_format);
+            if let Some(graph) = &target_graph {
+                parser = parser.with_default_graph(graph.clone());
+            }
+            match self.store.load_from_reader(parser, Cursor::new(data.as_bytes())) {
+                Ok(()) => Vec::new(),
+                Err(e) => vec![ParseIssue { line: 0, column: 0, message: e.to_string() }],
+            }
+        } else {
+            self.load_with_recovery(data, rdf_format, target_graph)?
+        };
+        Ok(issues)
+    }
+
+    fn rdf_format_from_name(name: &str) -> Result<RdfFormat, EngineError> {
+        match name {
+            "turtle" => Ok(RdfFormat::Turtle),
+            "ntriples" => Ok(RdfFormat::NTriples),
+            "nquads" => Ok(RdfFormat::NQuads),
+            "trig" => Ok(RdfFormat::TriG),
+            "rdfxml" => Ok(RdfFormat::RdfXml),
+            "n3" => Ok(RdfFormat::N3),
+            other => Err(EngineError::msg(format!("unsupported RDF format: {other}"))),
+        }
+    }
+
+    /// Streams `data` statement-by-statement, inserting each successfully parsed quad
+    /// and collecting (rather than aborting on) every syntax error encountered.
+    fn load_with_recovery(
+        &mut self,
+        data: &str,
+        format: RdfFormat,
+        target_graph: Option<NamedNode>,
+    ) -> Result<Vec<ParseIssue>, EngineError> {
+        let mut issues = Vec::new();
+        let mut parser = RdfParser::from_format(format);
+        if let Some(graph) = target_graph {
+            parser = parser.with_default_graph(graph);
         }
-        Ok(true)
+        let parser = parser.for_reader(Cursor::new(data.as_bytes()));
+        for result in parser {
+            match result {
+                Ok(quad) => {
+                    self.store.insert(&quad)?;
+                }
+                Err(e) => {
+                    // Only syntax errors carry a position; an I/O error (which can't
+                    // happen reading from an in-memory Cursor) has none to report.
+                    let position = match &e {
+                        RdfParseError::Syntax(syntax) => syntax.location().map(|range| range.start),
+                        RdfParseError::Io(_) => None,
+                    };
+                    let (line, column) = position.map_or((0, 0), |p| (p.line, p.column));
+                    issues.push(ParseIssue {
+                        line: line + 1,
+                        column: column + 1,
+                        message: e.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(issues)
+    }
+
+    // Supports INSERT DATA, DELETE DATA, DELETE/INSERT...WHERE, LOAD, CLEAR, DROP,
+    // and WITH/USING graph scoping, unlike ingest_rdf which can only ever add
+    // triples. Returns the number of triples inserted/deleted.
+    #[wasm_bindgen]
+    pub fn execute_sparql_update(&mut self, update: &str) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.execute_sparql_update_report(update)?)?)
+    }
+
+    fn execute_sparql_update_report(&mut self, update: &str) -> Result<UpdateReport, EngineError> {
+        let report = if let Some((is_insert, data)) = Self::as_ground_update(update) {
+            // INSERT DATA / DELETE DATA name their own quads, so the change count
+            // comes straight from applying them instead of diffing the whole store.
+            let count = self.apply_ground_quads(data, is_insert)?;
+            if is_insert {
+                UpdateReport { inserted: count, deleted: 0 }
+            } else {
+                UpdateReport { inserted: 0, deleted: count }
+            }
+        } else {
+            // DELETE/INSERT...WHERE, LOAD, CLEAR, DROP and multi-statement requests
+            // touch a data-dependent set of quads, so fall back to the general
+            // evaluator and report the net store-size change instead of
+            // materializing every quad to diff two copies of the whole store. This
+            // is a net delta, not true insert/delete counts: an update that deletes
+            // and inserts the same number of quads (e.g. replacing a value) reports
+            // {inserted: 0, deleted: 0} even though quads changed.
+            let parsed = Update::parse(update, None)?;
+            let before = self.store.len()? as i64;
+            self.store.update(parsed)?;
+            let after = self.store.len()? as i64;
+            let delta = after - before;
+            UpdateReport { inserted: delta.max(0) as u64, deleted: (-delta).max(0) as u64 }
+        };
+        Ok(report)
+    }
+
+    // Recognizes a bare `INSERT DATA { ... }`/`DELETE DATA { ... }` request with no
+    // other top-level statement (the common single-edit case from a WASM client)
+    // and returns its quad block. Braces are depth-counted so a nested `GRAPH <g>
+    // { ... }` block doesn't get mistaken for the end of the statement; anything
+    // left over after the matching `}` (a second statement) falls back to the
+    // general evaluator instead of silently truncating it.
+    //
+    // The counter walks raw characters, so a `}`/`{` inside a quoted string literal
+    // or a `#` comment can still throw off the brace depth. That's only ever safe
+    // because of the trailer check above: a misdetected boundary leaves a non-empty
+    // (and almost certainly non-`;`-prefixed) trailer, which routes the whole
+    // request to the general evaluator instead of mis-parsing it. If this function
+    // is ever taught to tolerate a non-empty trailer, the scanner needs real
+    // string/comment awareness first.
+    fn as_ground_update(update: &str) -> Option<(bool, &str)> {
+        let trimmed = update.trim();
+        let (is_insert, rest) = if let Some(rest) = trimmed.strip_prefix("INSERT DATA") {
+            (true, rest)
+        } else if let Some(rest) = trimmed.strip_prefix("DELETE DATA") {
+            (false, rest)
+        } else {
+            return None;
+        };
+        let rest = rest.trim_start();
+        if !rest.starts_with('{') {
+            return None;
+        }
+        let mut depth = 0i32;
+        let mut end = None;
+        for (idx, ch) in rest.char_indices() {
+            match ch {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(idx);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let end = end?;
+        let body = &rest[1..end];
+        let trailer = rest[end + 1..].trim();
+        let trailer = trailer.strip_prefix(';').unwrap_or(trailer).trim();
+        if !trailer.is_empty() {
+            return None;
+        }
+        Some((is_insert, body))
+    }
+
+    // Applies the TriG-shaped quad block from an INSERT DATA/DELETE DATA body
+    // directly, without going through the general update evaluator.
+    fn apply_ground_quads(&mut self, data: &str, is_insert: bool) -> Result<u64, EngineError> {
+        let mut count = 0u64;
+        let parser = RdfParser::from_format(RdfFormat::TriG).for_reader(Cursor::new(data.as_bytes()));
+        for quad in parser {
+            let quad = quad?;
+            let changed = if is_insert { self.store.insert(&quad)? } else { self.store.remove(&quad)? };
+            if changed {
+                count += 1;
+            }
+        }
+        Ok(count)
     }
 
     #[wasm_bindgen]
     pub fn execute_validation(&self, ttl: &str) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.execute_validation_messages(ttl)?)?)
+    }
+
+    fn execute_validation_messages(&self, ttl: &str) -> Result<Vec<String>, EngineError> {
         let mut graph = Graph::new();
-        let cursor = Cursor::new(ttl.as_bytes());
-        graph.read_from(cursor, GraphFormat::Turtle)?;
+        let parser = RdfParser::from_format(RdfFormat::Turtle).for_reader(Cursor::new(ttl.as_bytes()));
+        for quad in parser {
+            let quad = quad?;
+            graph.insert(Triple::new(quad.subject, quad.predicate, quad.object).as_ref());
+        }
 
         // Basic validation rules
         let mut validation_results = Vec::new();
-        
-        // Check for required properties
-        let required_props = ["rdfs:label", "rdfs:comment"];
+
+        // Check for required properties (the predicate is always a named node).
+        const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+        const RDFS_COMMENT: &str = "http://www.w3.org/2000/01/rdf-schema#comment";
+        const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+        const OWL_CLASS: &str = "http://www.w3.org/2002/07/owl#Class";
+
+        let required_props = [RDFS_LABEL, RDFS_COMMENT, RDF_TYPE];
         for triple in graph.iter() {
-            if let Some(pred) = triple.predicate.as_named() {
-                if !required_props.contains(&pred.as_str()) {
-                    validation_results.push(format!(
-                        "Warning: Triple {} {} {} uses non-standard predicate",
-                        triple.subject, triple.predicate, triple.object
-                    ));
-                }
+            if !required_props.contains(&triple.predicate.as_str()) {
+                validation_results.push(format!(
+                    "Warning: Triple {} {} {} uses non-standard predicate",
+                    triple.subject, triple.predicate, triple.object
+                ));
             }
         }
 
         // Check for class definitions
         let has_classes = graph.iter().any(|t| {
-            t.predicate.as_named().map_or(false, |p| p.as_str() == "rdf:type") &&
-            t.object.as_named().map_or(false, |o| o.as_str() == "owl:Class")
+            t.predicate.as_str() == RDF_TYPE
+                && matches!(t.object, TermRef::NamedNode(o) if o.as_str() == OWL_CLASS)
         });
 
         if !has_classes {
             validation_results.push("Error: No owl:Class definitions found".to_string());
         }
 
-        Ok(serde_wasm_bindgen::to_value(&validation_results)?)
+        Ok(validation_results)
+    }
+
+    // Compares two Turtle fragments for equality modulo blank node relabeling.
+    #[wasm_bindgen]
+    pub fn graphs_isomorphic(&self, a_ttl: &str, b_ttl: &str) -> Result<bool, JsError> {
+        Ok(isomorphism::find_mapping(a_ttl, b_ttl).map_err(JsError::from)?.is_some())
+    }
+
+    // Like graphs_isomorphic, but also returns the blank-node-label-to-label mapping
+    // that witnesses the isomorphism (or null if the graphs don't match).
+    #[wasm_bindgen]
+    pub fn graphs_isomorphic_mapping(&self, a_ttl: &str, b_ttl: &str) -> Result<JsValue, JsError> {
+        let mapping = isomorphism::find_mapping(a_ttl, b_ttl).map_err(JsError::from)?;
+        Ok(serde_wasm_bindgen::to_value(&mapping)?)
+    }
+
+    // Runs a W3C-style test manifest and reports pass/fail/error counts. `resolve`
+    // is a JS `(uri: string) => string` callback used to fetch qt:query/qt:data/
+    // mf:result/ut:request file contents.
+    #[wasm_bindgen]
+    pub fn run_manifest(&self, manifest_ttl: &str, resolve: &js_sys::Function) -> Result<JsValue, JsError> {
+        let report = conformance::run_manifest(manifest_ttl, resolve)?;
+        Ok(serde_wasm_bindgen::to_value(&report)?)
+    }
+
+    // Lists the IRIs of every named graph currently in the store (the default
+    // graph is not included).
+    #[wasm_bindgen]
+    pub fn list_named_graphs(&self) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.list_named_graph_names()?)?)
+    }
+
+    fn list_named_graph_names(&self) -> Result<Vec<String>, EngineError> {
+        self.store
+            .named_graphs()
+            .map(|g| {
+                Ok(match g? {
+                    oxigraph::model::NamedOrBlankNode::NamedNode(n) => n.into_string(),
+                    oxigraph::model::NamedOrBlankNode::BlankNode(b) => format!("_:{}", b.into_string()),
+                })
+            })
+            .collect()
+    }
+
+    // Removes every quad in the named graph `graph_name` and deregisters the graph
+    // itself, leaving other graphs untouched: `graph_name` no longer appears in
+    // list_named_graphs() afterwards.
+    #[wasm_bindgen]
+    pub fn clear_named_graph(&mut self, graph_name: &str) -> Result<(), JsError> {
+        self.clear_named_graph_inner(graph_name)?;
+        Ok(())
+    }
+
+    fn clear_named_graph_inner(&mut self, graph_name: &str) -> Result<(), EngineError> {
+        let graph = NamedNode::new(graph_name)?;
+        self.store.remove_named_graph(&graph)?;
+        Ok(())
+    }
+
+    // Serializes every triple in the named graph `graph_name` as `format`
+    // ("turtle" or "ntriples"), dropping the graph name itself from the output.
+    #[wasm_bindgen]
+    pub fn export_named_graph(&self, graph_name: &str, format: &str) -> Result<JsValue, JsError> {
+        Ok(serde_wasm_bindgen::to_value(&self.export_named_graph_str(graph_name, format)?)?)
+    }
+
+    fn export_named_graph_str(&self, graph_name: &str, format: &str) -> Result<String, EngineError> {
+        let graph = NamedNode::new(graph_name)?;
+        let rdf_format = Self::graph_result_format_from_name(format)?;
+        let mut output = Vec::new();
+        let mut writer = RdfSerializer::from_format(rdf_format).for_writer(&mut output);
+        for quad in self.store.quads_for_pattern(None, None, None, Some(graph.as_ref().into())) {
+            let quad = quad?;
+            writer.serialize_triple(Triple::new(quad.subject, quad.predicate, quad.object).as_ref())?;
+        }
+        writer.finish()?;
+        let text = String::from_utf8(output)?;
+        Ok(text)
+    }
+}
+
+// Blank-node-aware graph equality: Weisfeiler-Leman color refinement, then
+// backtracking over nodes that still share a color.
+pub(crate) mod isomorphism {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    type Color = u64;
+
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    enum Endpoint {
+        Ground(String),
+        Blank(usize),
+    }
+
+    struct LabeledGraph {
+        blank_labels: Vec<String>,
+        /// Triples with neither endpoint blank, as ground strings (fast-reject and
+        /// exact-match set; they can't be affected by any blank node relabeling).
+        ground_triples: Vec<(String, String, String)>,
+        /// Triples touching at least one blank node.
+        blank_triples: Vec<(Endpoint, String, Endpoint)>,
+    }
+
+    fn parse(ttl: &str) -> Result<LabeledGraph, EngineError> {
+        let mut blank_index: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        let mut blank_labels = Vec::new();
+        let mut ground_triples = Vec::new();
+        let mut blank_triples = Vec::new();
+
+        let mut endpoint_of = |term_as_blank: Option<&str>, term_as_string: String| -> Endpoint {
+            match term_as_blank {
+                Some(id) => {
+                    let idx = *blank_index.entry(id.to_string()).or_insert_with(|| {
+                        blank_labels.push(format!("_:{id}"));
+                        blank_labels.len() - 1
+                    });
+                    Endpoint::Blank(idx)
+                }
+                None => Endpoint::Ground(term_as_string),
+            }
+        };
+
+        let parser = RdfParser::from_format(RdfFormat::Turtle).for_reader(Cursor::new(ttl.as_bytes()));
+        for quad in parser {
+            let quad = quad?;
+            let subject_blank = match &quad.subject {
+                Subject::BlankNode(b) => Some(b.as_str()),
+                _ => None,
+            };
+            let object_blank = match &quad.object {
+                Term::BlankNode(b) => Some(b.as_str()),
+                _ => None,
+            };
+            if subject_blank.is_none() && object_blank.is_none() {
+                ground_triples.push((quad.subject.to_string(), quad.predicate.to_string(), quad.object.to_string()));
+            } else {
+                let s = endpoint_of(subject_blank, quad.subject.to_string());
+                let o = endpoint_of(object_blank, quad.object.to_string());
+                blank_triples.push((s, quad.predicate.to_string(), o));
+            }
+        }
+
+        Ok(LabeledGraph { blank_labels, ground_triples, blank_triples })
+    }
+
+    fn hash_of<T: Hash>(value: T) -> Color {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn endpoint_color(endpoint: &Endpoint, colors: &[Color]) -> Color {
+        match endpoint {
+            Endpoint::Ground(s) => hash_of(s),
+            Endpoint::Blank(i) => colors[*i],
+        }
+    }
+
+    // One refinement round: each blank node's new color hashes its old color with
+    // the sorted (predicate, direction, neighbor-color) multiset it appears in.
+    fn refine(graph: &LabeledGraph, colors: &mut Vec<Color>) -> bool {
+        let mut signatures: Vec<Vec<(String, u8, Color)>> = vec![Vec::new(); colors.len()];
+        for (s, p, o) in &graph.blank_triples {
+            if let Endpoint::Blank(i) = s {
+                signatures[*i].push((p.clone(), 0, endpoint_color(o, colors)));
+            }
+            if let Endpoint::Blank(i) = o {
+                signatures[*i].push((p.clone(), 1, endpoint_color(s, colors)));
+            }
+        }
+
+        let mut changed = false;
+        let mut next = colors.clone();
+        for i in 0..colors.len() {
+            signatures[i].sort();
+            let new_color = hash_of((colors[i], &signatures[i]));
+            if new_color != next[i] {
+                changed = true;
+            }
+            next[i] = new_color;
+        }
+        *colors = next;
+        changed
+    }
+
+    fn stable_colors(graph: &LabeledGraph) -> Vec<Color> {
+        let placeholder = hash_of("blank-node-placeholder");
+        let mut colors = vec![placeholder; graph.blank_labels.len()];
+        // A color partition over n nodes can refine at most n times before stabilizing.
+        for _ in 0..=graph.blank_labels.len() {
+            if !refine(graph, &mut colors) {
+                break;
+            }
+        }
+        colors
+    }
+
+    fn apply(endpoint: &Endpoint, mapping: &[usize]) -> Endpoint {
+        match endpoint {
+            Endpoint::Ground(s) => Endpoint::Ground(s.clone()),
+            Endpoint::Blank(i) => Endpoint::Blank(mapping[*i]),
+        }
+    }
+
+    fn multiset(triples: &[(Endpoint, String, Endpoint)]) -> std::collections::HashMap<(Endpoint, String, Endpoint), usize> {
+        let mut counts = std::collections::HashMap::new();
+        for (s, p, o) in triples {
+            *counts.entry((s.clone(), p.clone(), o.clone())).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // Backtracks over same-colored blank nodes for a bijection a -> b that makes
+    // a's blank triples, relabeled, equal b's blank triples as a multiset.
+    #[allow(clippy::too_many_arguments)]
+    fn search_bijection(
+        a: &LabeledGraph,
+        colors_a: &[Color],
+        order: &[usize],
+        pos: usize,
+        by_color_b: &std::collections::HashMap<Color, Vec<usize>>,
+        mapping: &mut Vec<Option<usize>>,
+        used_b: &mut std::collections::HashSet<usize>,
+        target: &std::collections::HashMap<(Endpoint, String, Endpoint), usize>,
+    ) -> Option<Vec<usize>> {
+        if pos == order.len() {
+            let resolved: Vec<usize> = mapping.iter().map(|m| m.unwrap()).collect();
+            let relabeled: Vec<(Endpoint, String, Endpoint)> = a
+                .blank_triples
+                .iter()
+                .map(|(s, p, o)| (apply(s, &resolved), p.clone(), apply(o, &resolved)))
+                .collect();
+            return if &multiset(&relabeled) == target { Some(resolved) } else { None };
+        }
+
+        let a_idx = order[pos];
+        let color = colors_a[a_idx];
+        for &b_idx in by_color_b.get(&color).into_iter().flatten() {
+            if used_b.contains(&b_idx) {
+                continue;
+            }
+            mapping[a_idx] = Some(b_idx);
+            used_b.insert(b_idx);
+            if let Some(found) = search_bijection(a, colors_a, order, pos + 1, by_color_b, mapping, used_b, target) {
+                return Some(found);
+            }
+            used_b.remove(&b_idx);
+            mapping[a_idx] = None;
+        }
+        None
+    }
+
+    // Finds a blank-node relabeling from a_ttl to b_ttl that makes the two graphs
+    // identical, or None if they aren't isomorphic.
+    pub(crate) fn find_mapping(a_ttl: &str, b_ttl: &str) -> Result<Option<std::collections::HashMap<String, String>>, EngineError> {
+        let a = parse(a_ttl)?;
+        let b = parse(b_ttl)?;
+
+        let total_a = a.ground_triples.len() + a.blank_triples.len();
+        let total_b = b.ground_triples.len() + b.blank_triples.len();
+        if total_a != total_b || a.blank_labels.len() != b.blank_labels.len() {
+            return Ok(None);
+        }
+
+        let mut ground_a = a.ground_triples.clone();
+        let mut ground_b = b.ground_triples.clone();
+        ground_a.sort();
+        ground_b.sort();
+        if ground_a != ground_b {
+            return Ok(None);
+        }
+
+        let colors_a = stable_colors(&a);
+        let colors_b = stable_colors(&b);
+
+        let mut by_color_b: std::collections::HashMap<Color, Vec<usize>> = std::collections::HashMap::new();
+        for (idx, color) in colors_b.iter().enumerate() {
+            by_color_b.entry(*color).or_default().push(idx);
+        }
+
+        let mut counts_a: std::collections::HashMap<Color, usize> = std::collections::HashMap::new();
+        for color in &colors_a {
+            *counts_a.entry(*color).or_insert(0) += 1;
+        }
+        for (color, count) in &counts_a {
+            if by_color_b.get(color).map_or(0, |v| v.len()) != *count {
+                return Ok(None);
+            }
+        }
+
+        let order: Vec<usize> = (0..a.blank_labels.len()).collect();
+        let mut mapping = vec![None; a.blank_labels.len()];
+        let mut used_b = std::collections::HashSet::new();
+        let target = multiset(&b.blank_triples);
+
+        let resolved = search_bijection(&a, &colors_a, &order, 0, &by_color_b, &mut mapping, &mut used_b, &target);
+        Ok(resolved.map(|resolved| {
+            resolved
+                .into_iter()
+                .enumerate()
+                .map(|(a_idx, b_idx)| (a.blank_labels[a_idx].clone(), b.blank_labels[b_idx].clone()))
+                .collect()
+        }))
     }
 }
 
@@ -99,8 +659,9 @@ mod tests {
 
     #[test]
     fn test_basic_validation() {
-        let engine = RDFEngine::new();
+        let engine = RDFEngine::new().unwrap();
         let ttl = r#"
+            @prefix : <http://example.org/> .
             @prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
             @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
             @prefix owl: <http://www.w3.org/2002/07/owl#> .
@@ -109,9 +670,176 @@ mod tests {
                 rdfs:label "Test Class" ;
                 rdfs:comment "A test class" .
         "#;
-        
-        let results = engine.execute_validation(ttl).unwrap();
-        let results: Vec<String> = serde_wasm_bindgen::from_value(results).unwrap();
+
+        let results = engine.execute_validation_messages(ttl).unwrap();
         assert!(results.is_empty());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn ingest_rdf_inserts_valid_turtle() {
+        let mut engine = RDFEngine::new().unwrap();
+        let data = "@prefix : <http://example.org/> .\n:a :knows :b .";
+        let issues = engine.ingest_rdf_issues(data, "turtle", None).unwrap();
+        assert!(issues.is_empty());
+        assert_eq!(engine.store.len().unwrap(), 1);
+    }
+
+    #[test]
+    fn ingest_rdf_recovers_from_a_bad_statement() {
+        let mut engine = RDFEngine::new().unwrap();
+        let data = "@prefix : <http://example.org/> .\n:a :knows :b .\n:this is not valid turtle .\n:c :knows :d .";
+        let issues = engine.ingest_rdf_issues(data, "turtle", None).unwrap();
+        assert_eq!(issues.len(), 1);
+        // The statements surrounding the bad one still parsed and were inserted.
+        assert_eq!(engine.store.len().unwrap(), 2);
+    }
+
+    #[test]
+    fn execute_sparql_update_insert_data_counts_exact_quads() {
+        let mut engine = RDFEngine::new().unwrap();
+        let update = "INSERT DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> . \
+            <http://example.org/a> <http://example.org/knows> <http://example.org/c> . }";
+        let report = engine.execute_sparql_update_report(update).unwrap();
+        assert_eq!((report.inserted, report.deleted), (2, 0));
+        assert_eq!(engine.store.len().unwrap(), 2);
+    }
+
+    #[test]
+    fn as_ground_update_does_not_mistake_a_nested_graph_block_for_the_end() {
+        // Regression test: the brace-matching must depth-count so a GRAPH block
+        // nested inside INSERT DATA doesn't get mistaken for the statement's end.
+        let update = "INSERT DATA { GRAPH <http://example.org/g> { \
+            <http://example.org/a> <http://example.org/knows> <http://example.org/b> } }";
+        let (is_insert, body) = RDFEngine::as_ground_update(update).unwrap();
+        assert!(is_insert);
+        assert!(body.trim().starts_with("GRAPH"));
+    }
+
+    #[test]
+    fn as_ground_update_rejects_a_trailing_second_statement() {
+        // Regression test: a second top-level statement after the matched `}`
+        // must fall back to the general evaluator instead of being truncated.
+        let update = "INSERT DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> } ; \
+            DELETE DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> }";
+        assert!(RDFEngine::as_ground_update(update).is_none());
+    }
+
+    #[test]
+    fn execute_sparql_update_where_driven_replace_reports_net_delta() {
+        let mut engine = RDFEngine::new().unwrap();
+        engine
+            .execute_sparql_update_report("PREFIX : <http://example.org/> INSERT DATA { :a :age 42 . }")
+            .unwrap();
+        let report = engine
+            .execute_sparql_update_report(
+                "PREFIX : <http://example.org/> DELETE { :a :age ?o } INSERT { :a :age 43 } WHERE { :a :age ?o }",
+            )
+            .unwrap();
+        // One triple deleted, one inserted: net store size is unchanged, which is
+        // the documented limitation of the net-delta fallback.
+        assert_eq!((report.inserted, report.deleted), (0, 0));
+        assert_eq!(engine.store.len().unwrap(), 1);
+    }
+
+    #[test]
+    fn execute_query_select_as_json() {
+        let mut engine = RDFEngine::new().unwrap();
+        engine
+            .execute_sparql_update_report("INSERT DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> . }")
+            .unwrap();
+        let result = engine.execute_query_str("SELECT * WHERE { ?s ?p ?o }", "json").unwrap();
+        assert!(result.contains("\"s\""));
+        assert!(result.contains("http://example.org/a"));
+    }
+
+    #[test]
+    fn execute_query_select_as_csv() {
+        let mut engine = RDFEngine::new().unwrap();
+        engine
+            .execute_sparql_update_report("INSERT DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> . }")
+            .unwrap();
+        let result = engine.execute_query_str("SELECT ?s WHERE { ?s ?p ?o }", "csv").unwrap();
+        assert!(result.contains("http://example.org/a"));
+    }
+
+    #[test]
+    fn execute_query_construct_as_turtle() {
+        let mut engine = RDFEngine::new().unwrap();
+        engine
+            .execute_sparql_update_report("INSERT DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> . }")
+            .unwrap();
+        let result = engine.execute_query_str("CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o }", "turtle").unwrap();
+        assert!(result.contains("http://example.org/a"));
+    }
+
+    #[test]
+    fn execute_query_rejects_unsupported_results_format() {
+        let engine = RDFEngine::new().unwrap();
+        assert!(engine.execute_query_str("SELECT * WHERE { ?s ?p ?o }", "yaml").is_err());
+    }
+
+    #[test]
+    fn named_graph_list_clear_and_export_round_trip() {
+        let mut engine = RDFEngine::new().unwrap();
+        engine
+            .ingest_rdf_issues(
+                "<http://example.org/a> <http://example.org/knows> <http://example.org/b> .",
+                "turtle",
+                Some("http://example.org/g1".to_string()),
+            )
+            .unwrap();
+        engine
+            .ingest_rdf_issues(
+                "<http://example.org/c> <http://example.org/knows> <http://example.org/d> .",
+                "turtle",
+                Some("http://example.org/g2".to_string()),
+            )
+            .unwrap();
+
+        let mut names = engine.list_named_graph_names().unwrap();
+        names.sort();
+        assert_eq!(names, vec!["http://example.org/g1", "http://example.org/g2"]);
+
+        let export = engine.export_named_graph_str("http://example.org/g1", "turtle").unwrap();
+        assert!(export.contains("http://example.org/a"));
+        assert!(!export.contains("http://example.org/c"));
+
+        engine.clear_named_graph_inner("http://example.org/g1").unwrap();
+        let names = engine.list_named_graph_names().unwrap();
+        assert_eq!(names, vec!["http://example.org/g2"]);
+    }
+
+    const PREFIX: &str = "@prefix : <http://example.org/> .\n";
+
+    #[test]
+    fn isomorphic_identical_graphs_match() {
+        let ttl = format!("{PREFIX} _:a :knows :bob . :bob :age 42 .");
+        let mapping = isomorphism::find_mapping(&ttl, &ttl).unwrap();
+        assert_eq!(mapping.unwrap().get("_:a").map(String::as_str), Some("_:a"));
+    }
+
+    #[test]
+    fn isomorphic_blank_node_relabeling_matches() {
+        let a = format!("{PREFIX} _:x :knows :bob .");
+        let b = format!("{PREFIX} _:y :knows :bob .");
+        let mapping = isomorphism::find_mapping(&a, &b).unwrap().unwrap();
+        assert_eq!(mapping.get("_:x").map(String::as_str), Some("_:y"));
+    }
+
+    #[test]
+    fn ground_triple_mismatch_is_not_isomorphic() {
+        let a = format!("{PREFIX} _:x :knows :bob .");
+        let b = format!("{PREFIX} _:y :knows :alice .");
+        assert!(isomorphism::find_mapping(&a, &b).unwrap().is_none());
+    }
+
+    #[test]
+    fn same_color_class_requires_backtracking() {
+        // Both blank nodes in each graph are structurally indistinguishable (same
+        // color after refinement), so a correct mapping can only be found by
+        // backtracking over which same-colored candidate to pair with which.
+        let a = format!("{PREFIX} _:a :knows _:b . _:b :knows _:a .");
+        let b = format!("{PREFIX} _:x :knows _:y . _:y :knows _:x .");
+        assert!(isomorphism::find_mapping(&a, &b).unwrap().is_some());
+    }
+}
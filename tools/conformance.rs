@@ -0,0 +1,337 @@
+// Runs RDFEngine against W3C-style test manifests.
+
+use crate::RDFEngine;
+use js_sys::Function;
+use oxigraph::io::{RdfFormat, RdfParser};
+use oxigraph::model::{Graph, Term};
+use oxigraph::sparql::results::QueryResultsFormat;
+use oxigraph::sparql::{QueryResults, QuerySolution};
+use serde::Serialize;
+use std::io::Cursor;
+use wasm_bindgen::{JsError, JsValue};
+
+const MF: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-manifest#";
+const QT: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-query#";
+const UT: &str = "http://www.w3.org/2009/sparql/tests/test-update#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+
+#[derive(Serialize)]
+pub struct TestOutcome {
+    pub test_uri: String,
+    pub status: TestStatus,
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, PartialEq, Eq)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Errored,
+}
+
+#[derive(Serialize)]
+pub struct ConformanceReport {
+    pub total: u64,
+    pub passed: u64,
+    pub failed: u64,
+    pub errored: u64,
+    pub results: Vec<TestOutcome>,
+}
+
+/// A test couldn't be evaluated at all (manifest structure or file resolution),
+/// vs. ran fine and produced the wrong answer.
+enum TestProblem {
+    Errored(String),
+    Failed(String),
+}
+
+// Loads manifest_ttl as a mf:Manifest graph and runs every mf:entries test against
+// a fresh RDFEngine. `resolve` is a JS callback `(uri: string) => string` that the
+// host uses to fetch the contents of qt:query/qt:data/mf:result/ut:request URIs —
+// this crate has no filesystem or network access of its own in the browser.
+pub fn run_manifest(manifest_ttl: &str, resolve: &Function) -> Result<ConformanceReport, JsError> {
+    let mut graph = Graph::new();
+    let parser = RdfParser::from_format(RdfFormat::Turtle).for_reader(Cursor::new(manifest_ttl.as_bytes()));
+    for quad in parser {
+        graph.insert(quad?.as_ref());
+    }
+
+    let entries_head = first_object(&graph, &format!("{MF}entries"))
+        .ok_or_else(|| JsError::new("manifest has no mf:entries list"))?;
+    let mut results = Vec::new();
+    for entry in rdf_list(&graph, &entries_head) {
+        results.push(run_entry(&graph, &entry, resolve));
+    }
+
+    let passed = results.iter().filter(|r| r.status == TestStatus::Passed).count() as u64;
+    let failed = results.iter().filter(|r| r.status == TestStatus::Failed).count() as u64;
+    let errored = results.iter().filter(|r| r.status == TestStatus::Errored).count() as u64;
+    Ok(ConformanceReport { total: results.len() as u64, passed, failed, errored, results })
+}
+
+fn run_entry(graph: &Graph, entry: &Term, resolve: &Function) -> TestOutcome {
+    let test_uri = entry.to_string();
+    let kind = object_for(graph, entry, RDF_TYPE).map(|t| t.to_string()).unwrap_or_default();
+
+    let outcome = match kind.as_str() {
+        "<http://www.w3.org/2001/sw/DataAccess/tests/test-query#PositiveSyntaxTest11>" => {
+            run_positive_syntax_test(graph, entry, resolve)
+        }
+        "<http://www.w3.org/2001/sw/DataAccess/tests/test-query#QueryEvaluationTest>" => {
+            run_query_evaluation_test(graph, entry, resolve)
+        }
+        "<http://www.w3.org/2009/sparql/tests/test-update#UpdateEvaluationTest>" => {
+            run_update_evaluation_test(graph, entry, resolve)
+        }
+        other => Err(TestProblem::Errored(format!("unsupported test kind: {other}"))),
+    };
+
+    match outcome {
+        Ok(()) => TestOutcome { test_uri, status: TestStatus::Passed, detail: None },
+        Err(TestProblem::Failed(detail)) => {
+            TestOutcome { test_uri, status: TestStatus::Failed, detail: Some(detail) }
+        }
+        Err(TestProblem::Errored(detail)) => {
+            TestOutcome { test_uri, status: TestStatus::Errored, detail: Some(detail) }
+        }
+    }
+}
+
+fn run_positive_syntax_test(graph: &Graph, entry: &Term, resolve: &Function) -> Result<(), TestProblem> {
+    let action = require(object_for(graph, entry, &format!("{MF}action")), "entry has no mf:action")?;
+    let query_literal = resolve_file(resolve, &action.to_string()).map_err(TestProblem::Errored)?;
+    let engine = RDFEngine::new().map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+    // The test itself is the assertion that this parses without error.
+    engine.store.query(&query_literal).map(|_| ()).map_err(|e| TestProblem::Failed(e.to_string()))
+}
+
+fn run_query_evaluation_test(graph: &Graph, entry: &Term, resolve: &Function) -> Result<(), TestProblem> {
+    let action = require(object_for(graph, entry, &format!("{MF}action")), "entry has no mf:action")?;
+    let query_path = require(object_for(graph, &action, &format!("{QT}query")), "qt:query missing")?;
+    let data_path = object_for(graph, &action, &format!("{QT}data"));
+
+    let mut engine = RDFEngine::new().map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+    if let Some(data_path) = data_path {
+        let data = resolve_file(resolve, &data_path.to_string()).map_err(TestProblem::Errored)?;
+        engine.ingest_rdf(&data, "turtle", None).map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+    }
+    let query = resolve_file(resolve, &query_path.to_string()).map_err(TestProblem::Errored)?;
+    let actual = engine.store.query(&query).map_err(|e| TestProblem::Errored(e.to_string()))?;
+
+    let expected_path = require(object_for(graph, entry, &format!("{MF}result")), "mf:result missing")?;
+    let expected = resolve_file(resolve, &expected_path.to_string()).map_err(TestProblem::Errored)?;
+    compare_results(actual, &expected).map_err(TestProblem::Failed)
+}
+
+fn run_update_evaluation_test(graph: &Graph, entry: &Term, resolve: &Function) -> Result<(), TestProblem> {
+    let action = require(object_for(graph, entry, &format!("{MF}action")), "entry has no mf:action")?;
+    let request_path = require(object_for(graph, &action, &format!("{UT}request")), "ut:request missing")?;
+
+    let mut engine = RDFEngine::new().map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+    if let Some(data_path) = object_for(graph, &action, &format!("{UT}data")) {
+        let data = resolve_file(resolve, &data_path.to_string()).map_err(TestProblem::Errored)?;
+        engine.ingest_rdf(&data, "turtle", None).map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+    }
+    let request = resolve_file(resolve, &request_path.to_string()).map_err(TestProblem::Errored)?;
+    engine.execute_sparql_update(&request).map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+
+    let expected_path = require(object_for(graph, entry, &format!("{UT}result")), "ut:result missing")?;
+    let expected = resolve_file(resolve, &expected_path.to_string()).map_err(TestProblem::Errored)?;
+    let mut expected_engine = RDFEngine::new().map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+    expected_engine
+        .ingest_rdf(&expected, "turtle", None)
+        .map_err(|e| TestProblem::Errored(format!("{e:?}")))?;
+
+    let actual_dump = engine.store.iter().collect::<Result<Vec<_>, _>>().map_err(|e| TestProblem::Errored(e.to_string()))?;
+    let expected_dump = expected_engine
+        .store
+        .iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TestProblem::Errored(e.to_string()))?;
+    if actual_dump.len() != expected_dump.len() {
+        return Err(TestProblem::Failed(format!(
+            "expected {} quads, got {}",
+            expected_dump.len(),
+            actual_dump.len()
+        )));
+    }
+    for quad in &expected_dump {
+        if !actual_dump.contains(quad) {
+            return Err(TestProblem::Failed(format!("missing expected quad: {quad}")));
+        }
+    }
+    Ok(())
+}
+
+fn require<T>(value: Option<T>, message: &str) -> Result<T, TestProblem> {
+    value.ok_or_else(|| TestProblem::Errored(message.to_string()))
+}
+
+// Compares a query's actual results to an expected result document: graph
+// isomorphism for CONSTRUCT/DESCRIBE, boolean equality for ASK, and solution-set
+// comparison (parsing the expected document as SPARQL Results JSON or XML) for
+// SELECT.
+fn compare_results(actual: QueryResults, expected_ttl: &str) -> Result<(), String> {
+    match actual {
+        QueryResults::Graph(triples) => {
+            let mut actual_ttl = Vec::new();
+            {
+                use oxigraph::io::RdfSerializer;
+                let mut writer = RdfSerializer::from_format(RdfFormat::Turtle).for_writer(&mut actual_ttl);
+                for triple in triples {
+                    writer.serialize_triple(&triple.map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+                }
+                writer.finish().map_err(|e| e.to_string())?;
+            }
+            let actual_ttl = String::from_utf8(actual_ttl).map_err(|e| e.to_string())?;
+            let isomorphic = crate::isomorphism::find_mapping(&actual_ttl, expected_ttl)
+                .map_err(|e| format!("{e:?}"))?
+                .is_some();
+            if isomorphic { Ok(()) } else { Err("actual graph not isomorphic to expected result".to_string()) }
+        }
+        QueryResults::Boolean(value) => {
+            let expected = expected_ttl.trim() == "true";
+            if value == expected { Ok(()) } else { Err(format!("expected {expected}, got {value}")) }
+        }
+        QueryResults::Solutions(solutions) => {
+            let actual_rows = solution_rows(solutions)?;
+            let expected_format = if expected_ttl.trim_start().starts_with('{') {
+                QueryResultsFormat::Json
+            } else {
+                QueryResultsFormat::Xml
+            };
+            // QueryResults::read requires a `'static` reader, so hand it an owned
+            // buffer rather than a Cursor borrowing expected_ttl.
+            let expected_results = QueryResults::read(Cursor::new(expected_ttl.as_bytes().to_vec()), expected_format)
+                .map_err(|e| e.to_string())?;
+            let expected_rows = match expected_results {
+                QueryResults::Solutions(solutions) => solution_rows(solutions)?,
+                _ => return Err("expected result document is not a solution sequence".to_string()),
+            };
+            if actual_rows == expected_rows {
+                Ok(())
+            } else {
+                Err(format!("solution sequence did not match expected result: got {actual_rows:?}, expected {expected_rows:?}"))
+            }
+        }
+    }
+}
+
+// Collects a solution sequence into a form comparable across runs: each row's
+// bindings sorted by variable name, rows themselves sorted (solution order is
+// unspecified for unordered SELECT queries).
+fn solution_rows<E: ToString>(
+    solutions: impl Iterator<Item = Result<QuerySolution, E>>,
+) -> Result<Vec<Vec<(String, String)>>, String> {
+    let mut rows = Vec::new();
+    for solution in solutions {
+        let solution = solution.map_err(|e| e.to_string())?;
+        let mut row: Vec<(String, String)> =
+            solution.iter().map(|(var, term)| (var.as_str().to_string(), term.to_string())).collect();
+        row.sort();
+        rows.push(row);
+    }
+    rows.sort();
+    Ok(rows)
+}
+
+// Fetches a manifest file's contents via the host-provided `resolve(uri) -> string`
+// callback; this crate has no filesystem or network access of its own.
+fn resolve_file(resolve: &Function, uri: &str) -> Result<String, String> {
+    let result = resolve
+        .call1(&JsValue::NULL, &JsValue::from_str(uri))
+        .map_err(|e| format!("resolve({uri}) threw: {e:?}"))?;
+    result.as_string().ok_or_else(|| format!("resolve({uri}) did not return a string"))
+}
+
+fn first_object(graph: &Graph, predicate: &str) -> Option<Term> {
+    graph.iter().find(|t| t.predicate.as_str() == predicate).map(|t| t.object.into_owned())
+}
+
+fn object_for(graph: &Graph, subject: &Term, predicate: &str) -> Option<Term> {
+    graph
+        .iter()
+        .find(|t| subject_matches(&t.subject, subject) && t.predicate.as_str() == predicate)
+        .map(|t| t.object.into_owned())
+}
+
+fn subject_matches(subject: &oxigraph::model::SubjectRef, term: &Term) -> bool {
+    match (subject, term) {
+        (oxigraph::model::SubjectRef::NamedNode(n), Term::NamedNode(m)) => n.as_str() == m.as_str(),
+        (oxigraph::model::SubjectRef::BlankNode(n), Term::BlankNode(m)) => n.as_str() == m.as_str(),
+        _ => false,
+    }
+}
+
+fn rdf_list(graph: &Graph, head: &Term) -> Vec<Term> {
+    let mut items = Vec::new();
+    let mut current = head.clone();
+    loop {
+        if current.to_string() == format!("<{RDF_NIL}>") {
+            break;
+        }
+        match object_for(graph, &current, RDF_FIRST) {
+            Some(item) => items.push(item),
+            None => break,
+        }
+        match object_for(graph, &current, RDF_REST) {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+    items
+}
+
+// run_manifest itself needs a live JS callback and so can only be exercised from
+// a wasm-bindgen-test harness; these cover the comparison logic that decides
+// pass/fail for it.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::sparql::Update;
+    use oxigraph::store::Store;
+
+    fn select_people_who_know_someone() -> QueryResults {
+        let store = Store::new().unwrap();
+        store
+            .update(
+                Update::parse(
+                    "INSERT DATA { <http://example.org/a> <http://example.org/knows> <http://example.org/b> . \
+                     <http://example.org/c> <http://example.org/knows> <http://example.org/d> . }",
+                    None,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        store.query("SELECT ?s WHERE { ?s <http://example.org/knows> ?o }").unwrap()
+    }
+
+    #[test]
+    fn compare_results_matches_select_solutions_regardless_of_order() {
+        let expected = r#"{"head":{"vars":["s"]},"results":{"bindings":[
+            {"s":{"type":"uri","value":"http://example.org/c"}},
+            {"s":{"type":"uri","value":"http://example.org/a"}}
+        ]}}"#;
+        assert!(compare_results(select_people_who_know_someone(), expected).is_ok());
+    }
+
+    #[test]
+    fn compare_results_rejects_a_wrong_binding() {
+        let expected = r#"{"head":{"vars":["s"]},"results":{"bindings":[
+            {"s":{"type":"uri","value":"http://example.org/a"}},
+            {"s":{"type":"uri","value":"http://example.org/z"}}
+        ]}}"#;
+        assert!(compare_results(select_people_who_know_someone(), expected).is_err());
+    }
+
+    #[test]
+    fn compare_results_rejects_a_missing_binding() {
+        let expected = r#"{"head":{"vars":["s"]},"results":{"bindings":[
+            {"s":{"type":"uri","value":"http://example.org/a"}}
+        ]}}"#;
+        assert!(compare_results(select_people_who_know_someone(), expected).is_err());
+    }
+}